@@ -1,5 +1,7 @@
 use failure::Fail;
+use filter::Filter;
 use scheme::{Field, Scheme};
+use std::fmt;
 use types::{GetType, LhsValue, Type};
 
 /// An error that occurs if the type of the value for the field doesn't
@@ -16,6 +18,120 @@ pub struct FieldValueTypeMismatchError {
     pub value_type: Type,
 }
 
+/// Controls how an [`ExecutionContext`](struct@ExecutionContext) behaves
+/// when a filter references a field that wasn't given a runtime value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingValuePolicy {
+    /// Panic as soon as a missing field is touched during evaluation. This
+    /// is the historical behaviour and remains the default.
+    Strict,
+    /// Align with wireshark: treat a missing field as unsatisfiable, so any
+    /// comparison or function call touching it resolves the enclosing
+    /// boolean subexpression to `false` instead of panicking.
+    Lenient,
+}
+
+impl Default for MissingValuePolicy {
+    fn default() -> Self {
+        MissingValuePolicy::Strict
+    }
+}
+
+/// An error that occurs when [`ExecutionContext::set_field_value`] is given
+/// a field name that the associated [`Scheme`](struct@Scheme) doesn't know
+/// about.
+#[derive(Debug, PartialEq)]
+pub struct UnknownFieldError {
+    /// The field name that was passed in.
+    pub name: String,
+    /// The closest known field name, if one is close enough to plausibly be
+    /// a typo of `name`.
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnknownFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown field `{}`", self.name)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", did you mean `{}`?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl Fail for UnknownFieldError {}
+
+/// An error returned by [`ExecutionContext::set_field_value`].
+#[derive(Debug, PartialEq, Fail)]
+pub enum SetFieldValueError {
+    /// The field is known, but the provided value doesn't match its type.
+    #[fail(display = "{}", _0)]
+    TypeMismatch(#[cause] FieldValueTypeMismatchError),
+    /// The field name isn't known to the scheme.
+    #[fail(display = "{}", _0)]
+    UnknownField(#[cause] UnknownFieldError),
+}
+
+/// Computes the Damerau-Levenshtein edit distance between `a` and `b` in its
+/// restricted (optimal string alignment) form: the minimum number of
+/// insertions, deletions, substitutions and transpositions of adjacent
+/// characters needed to turn one into the other, where no substring is
+/// edited more than once (so `"ca"` to `"abc"` costs 3, not 2).
+fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (a_len, b_len) = (a.len(), b.len());
+
+    let mut d = vec![vec![0usize; b_len + 1]; a_len + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a_len {
+        for j in 1..=b_len {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a_len][b_len]
+}
+
+/// An error returned by [`ExecutionContext::validate`], listing every field
+/// a filter references that hasn't been given a runtime value yet.
+#[derive(Debug, PartialEq)]
+pub struct MissingFieldValuesError {
+    /// The missing fields, together with the type the filter expects each
+    /// of them to have.
+    pub fields: Vec<(String, Type)>,
+}
+
+impl fmt::Display for MissingFieldValuesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "context is missing values for fields: ")?;
+        for (i, (name, field_type)) in self.fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} ({:?})", name, field_type)?;
+        }
+        Ok(())
+    }
+}
+
+impl Fail for MissingFieldValuesError {}
+
 /// An execution context stores an associated [`Scheme`](struct@Scheme) and a
 /// set of runtime values to execute [`Filter`](::Filter) against.
 ///
@@ -24,39 +140,87 @@ pub struct FieldValueTypeMismatchError {
 pub struct ExecutionContext<'e> {
     scheme: &'e Scheme,
     values: Box<[Option<LhsValue<'e>>]>,
+    missing_value_policy: MissingValuePolicy,
 }
 
 impl<'e> ExecutionContext<'e> {
     /// Creates an execution context associated with a given scheme.
     ///
     /// This scheme will be used for resolving any field names and indices.
+    ///
+    /// Missing field values are handled according to
+    /// [`MissingValuePolicy::Strict`] by default; use
+    /// [`with_missing_value_policy`](ExecutionContext::with_missing_value_policy)
+    /// to opt into lenient, wireshark-style resolution instead.
     pub fn new<'s: 'e>(scheme: &'s Scheme) -> Self {
         ExecutionContext {
             scheme,
             values: vec![None; scheme.get_field_count()].into(),
+            missing_value_policy: MissingValuePolicy::default(),
         }
     }
 
+    /// Sets the [`MissingValuePolicy`] used when resolving fields that
+    /// weren't given a runtime value.
+    pub fn with_missing_value_policy(mut self, policy: MissingValuePolicy) -> Self {
+        self.missing_value_policy = policy;
+        self
+    }
+
     /// Returns an associated scheme.
     pub fn scheme(&self) -> &'e Scheme {
         self.scheme
     }
 
-    pub(crate) fn get_field_value_unchecked(&self, field: Field<'e>) -> &LhsValue<'e> {
-        // This is safe because this code is reachable only from Filter::execute
-        // which already performs the scheme compatibility check, but check that
-        // invariant holds in the future at least in the debug mode.
-        debug_assert!(self.scheme() == field.scheme());
+    /// Resolves `field`, which may belong to a different (but, per
+    /// `Scheme::is_compatible_with`, compatible) scheme than this context's,
+    /// to the equivalent field on `self.scheme()` — the one whose index
+    /// actually indexes `self.values`.
+    fn resolve_field(&self, field: Field<'e>) -> Field<'e> {
+        if field.scheme() == self.scheme() {
+            field
+        } else {
+            self.scheme()
+                .get_field_index(field.name())
+                .expect("field is not registered on the context's scheme")
+        }
+    }
+
+    pub(crate) fn get_field_value_unchecked(&self, field: Field<'e>) -> Option<&LhsValue<'e>> {
+        // This is safe because this code is reachable only from Filter::execute,
+        // which already performs the scheme compatibility check. `field.scheme()`
+        // no longer has to be this exact `Scheme` instance: `Filter::execute`
+        // only requires `self.scheme()` to be compatible with it (see
+        // `Scheme::is_compatible_with`), i.e. every field declared on the
+        // filter's scheme exists here with a matching `Type`. We still check that (relaxed)
+        // invariant here in debug builds.
+        debug_assert!(self.scheme().is_compatible_with(field.scheme()).is_ok());
 
-        // For now we panic in this, but later we are going to align behaviour
-        // with wireshark: resolve all subexpressions that don't have RHS value
-        // to `false`.
-        self.values[field.index()].as_ref().unwrap_or_else(|| {
+        // `field`'s index is only meaningful on its own scheme, which may not
+        // be `self.scheme()` — resolve it to the equivalent field here first.
+        let field = self.resolve_field(field);
+
+        // A value set directly on the context always takes priority; a field
+        // with no runtime value falls back to the default registered for it
+        // on the scheme (if any), so a filter compiled against a newer scheme
+        // can still run against a context that only populates a subset of it.
+        let value = self.values[field.index()]
+            .as_ref()
+            .or_else(|| field.default_value());
+
+        if value.is_none() && self.missing_value_policy == MissingValuePolicy::Strict {
             panic!(
                 "Field {} was registered but not given a value",
                 field.name()
             );
-        })
+        }
+
+        // In `Lenient` mode a missing value resolves to `None`, which the AST
+        // nodes evaluating comparisons and function calls interpret as
+        // "unsatisfiable", collapsing the enclosing subexpression to `false`
+        // rather than panicking. This matches wireshark's behaviour when
+        // matching against partial packets.
+        value
     }
 
     /// Sets a runtime value for a given field name.
@@ -64,8 +228,13 @@ impl<'e> ExecutionContext<'e> {
         &mut self,
         name: &str,
         value: V,
-    ) -> Result<(), FieldValueTypeMismatchError> {
-        let field = self.scheme.get_field_index(name).unwrap();
+    ) -> Result<(), SetFieldValueError> {
+        let field = self.scheme.get_field_index(name).ok_or_else(|| {
+            SetFieldValueError::UnknownField(UnknownFieldError {
+                name: name.to_owned(),
+                suggestion: self.suggest_field_name(name),
+            })
+        })?;
         let value = value.into();
 
         let field_type = field.get_type();
@@ -75,12 +244,68 @@ impl<'e> ExecutionContext<'e> {
             self.values[field.index()] = Some(value);
             Ok(())
         } else {
-            Err(FieldValueTypeMismatchError {
-                field_type,
-                value_type,
+            Err(SetFieldValueError::TypeMismatch(
+                FieldValueTypeMismatchError {
+                    field_type,
+                    value_type,
+                },
+            ))
+        }
+    }
+
+    /// Checks that every field referenced by `filter` has a runtime value
+    /// set in this context, without executing the filter.
+    ///
+    /// Unlike [`get_field_value_unchecked`](ExecutionContext::get_field_value_unchecked)
+    /// (only reachable through [`Filter::execute`]), which stops at the
+    /// first missing field, this walks every referenced field up front and
+    /// reports them all at once, so callers can tell a user everything they
+    /// forgot to set in one pass.
+    ///
+    /// A field is only reported as missing here if
+    /// [`get_field_value_unchecked`](ExecutionContext::get_field_value_unchecked)
+    /// would also treat it as missing during execution: a field with a
+    /// scheme-registered default is never missing, and under
+    /// [`MissingValuePolicy::Lenient`] no field is ever missing, since a
+    /// missing value there resolves to `false` rather than failing.
+    ///
+    /// This does not check scheme compatibility: under
+    /// [`MissingValuePolicy::Strict`] it panics if `filter` references a
+    /// field that isn't registered on this context's scheme.
+    pub fn validate(&self, filter: &Filter<'e>) -> Result<(), MissingFieldValuesError> {
+        if self.missing_value_policy == MissingValuePolicy::Lenient {
+            return Ok(());
+        }
+
+        let fields: Vec<_> = filter
+            .used_fields()
+            .into_iter()
+            .map(|field| self.resolve_field(field))
+            .filter(|field| {
+                self.values[field.index()].is_none() && field.default_value().is_none()
             })
+            .map(|field| (field.name().to_owned(), field.get_type()))
+            .collect();
+
+        if fields.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingFieldValuesError { fields })
         }
     }
+
+    /// Finds the known field name closest to `name` under a bounded
+    /// Damerau-Levenshtein distance, for use in "did you mean" suggestions
+    /// when [`set_field_value`](ExecutionContext::set_field_value) is given
+    /// an unknown field name.
+    fn suggest_field_name(&self, name: &str) -> Option<String> {
+        self.scheme
+            .iter()
+            .map(|(field_name, _)| (field_name, damerau_levenshtein(name, field_name)))
+            .filter(|&(_, distance)| distance <= (name.len() / 3).max(1))
+            .min_by_key(|&(_, distance)| distance)
+            .map(|(field_name, _)| field_name.to_owned())
+    }
 }
 
 #[test]
@@ -91,9 +316,98 @@ fn test_field_value_type_mismatch() {
 
     assert_eq!(
         ctx.set_field_value("foo", LhsValue::Bool(false)),
-        Err(FieldValueTypeMismatchError {
-            field_type: Type::Int,
-            value_type: Type::Bool
+        Err(SetFieldValueError::TypeMismatch(
+            FieldValueTypeMismatchError {
+                field_type: Type::Int,
+                value_type: Type::Bool
+            }
+        ))
+    );
+}
+
+#[test]
+fn test_set_field_value_unknown_field_suggestion() {
+    let scheme = Scheme! { foo: Int };
+
+    let mut ctx = ExecutionContext::new(&scheme);
+
+    assert_eq!(
+        ctx.set_field_value("fo", LhsValue::Int(1)),
+        Err(SetFieldValueError::UnknownField(UnknownFieldError {
+            name: "fo".to_owned(),
+            suggestion: Some("foo".to_owned()),
+        }))
+    );
+}
+
+#[test]
+fn test_set_field_value_unknown_field_no_suggestion() {
+    let scheme = Scheme! { foo: Int };
+
+    let mut ctx = ExecutionContext::new(&scheme);
+
+    assert_eq!(
+        ctx.set_field_value("completely_unrelated_name", LhsValue::Int(1)),
+        Err(SetFieldValueError::UnknownField(UnknownFieldError {
+            name: "completely_unrelated_name".to_owned(),
+            suggestion: None,
+        }))
+    );
+}
+
+#[test]
+#[should_panic(expected = "was registered but not given a value")]
+fn test_missing_value_strict_panics() {
+    let scheme = Scheme! { foo: Int };
+    let ctx = ExecutionContext::new(&scheme);
+
+    ctx.get_field_value_unchecked(scheme.get_field_index("foo").unwrap());
+}
+
+#[test]
+fn test_missing_value_lenient_resolves_to_unsatisfiable() {
+    let scheme = Scheme! { foo: Int };
+    let ctx =
+        ExecutionContext::new(&scheme).with_missing_value_policy(MissingValuePolicy::Lenient);
+
+    assert_eq!(
+        ctx.get_field_value_unchecked(scheme.get_field_index("foo").unwrap()),
+        None
+    );
+}
+
+#[test]
+fn test_validate_reports_missing_fields() {
+    let scheme = Scheme! { foo: Int, bar: Bool };
+    let ctx = ExecutionContext::new(&scheme);
+
+    let filter = Filter::Equals(scheme.get_field_index("foo").unwrap(), LhsValue::Int(1));
+
+    assert_eq!(
+        ctx.validate(&filter),
+        Err(MissingFieldValuesError {
+            fields: vec![("foo".to_owned(), Type::Int)]
         })
     );
 }
+
+#[test]
+fn test_validate_ignores_field_with_default() {
+    let mut scheme = Scheme! { foo: Int };
+    scheme.set_field_default("foo", LhsValue::Int(0)).unwrap();
+
+    let ctx = ExecutionContext::new(&scheme);
+    let filter = Filter::Equals(scheme.get_field_index("foo").unwrap(), LhsValue::Int(1));
+
+    assert_eq!(ctx.validate(&filter), Ok(()));
+}
+
+#[test]
+fn test_validate_ignores_missing_fields_under_lenient_policy() {
+    let scheme = Scheme! { foo: Int };
+    let ctx =
+        ExecutionContext::new(&scheme).with_missing_value_policy(MissingValuePolicy::Lenient);
+    let filter = Filter::Equals(scheme.get_field_index("foo").unwrap(), LhsValue::Int(1));
+
+    assert_eq!(ctx.validate(&filter), Ok(()));
+}
@@ -0,0 +1,76 @@
+use execution_context::ExecutionContext;
+use scheme::{Field, SchemeCompatibilityError};
+use types::LhsValue;
+
+/// A compiled filter expression, built against a particular
+/// [`Scheme`](::Scheme) and evaluated against a compatible
+/// [`ExecutionContext`](::ExecutionContext).
+pub enum Filter<'s> {
+    /// True when the named field has a value equal to the given constant.
+    Equals(Field<'s>, LhsValue<'static>),
+    /// True when both subexpressions are true.
+    And(Box<Filter<'s>>, Box<Filter<'s>>),
+    /// True when either subexpression is true.
+    Or(Box<Filter<'s>>, Box<Filter<'s>>),
+    /// True when the subexpression is false.
+    Not(Box<Filter<'s>>),
+}
+
+impl<'s> Filter<'s> {
+    /// Every field this filter references, used by
+    /// [`ExecutionContext::validate`](::ExecutionContext::validate) to
+    /// report all of a context's missing fields at once.
+    pub fn used_fields(&self) -> Vec<Field<'s>> {
+        match self {
+            Filter::Equals(field, _) => vec![*field],
+            Filter::And(lhs, rhs) | Filter::Or(lhs, rhs) => {
+                let mut fields = lhs.used_fields();
+                fields.extend(rhs.used_fields());
+                fields
+            }
+            Filter::Not(inner) => inner.used_fields(),
+        }
+    }
+
+    /// Checks that `ctx`'s scheme is compatible with the scheme this filter
+    /// was built against (see
+    /// [`Scheme::is_compatible_with`](::Scheme::is_compatible_with)), then
+    /// evaluates the filter against it.
+    ///
+    /// This is the only supported entry point for running a filter: unlike
+    /// the `debug_assert!` that guards
+    /// [`ExecutionContext::get_field_value_unchecked`](::ExecutionContext::get_field_value_unchecked)
+    /// internally (compiled out in release builds), the compatibility check
+    /// here always runs, so a filter executed against an incompatible
+    /// scheme returns `Err` instead of panicking.
+    ///
+    /// A comparison touching a field with no runtime value only panics
+    /// under [`MissingValuePolicy::Strict`](::MissingValuePolicy::Strict);
+    /// under [`MissingValuePolicy::Lenient`](::MissingValuePolicy::Lenient)
+    /// it's unsatisfiable and collapses the enclosing subexpression to
+    /// `false`, matching wireshark's behaviour for partial packets.
+    pub fn execute<'e>(&self, ctx: &ExecutionContext<'e>) -> Result<bool, SchemeCompatibilityError>
+    where
+        's: 'e,
+    {
+        if let Some(field) = self.used_fields().first() {
+            ctx.scheme().is_compatible_with(field.scheme())?;
+        }
+
+        Ok(self.execute_unchecked(ctx))
+    }
+
+    fn execute_unchecked<'e>(&self, ctx: &ExecutionContext<'e>) -> bool
+    where
+        's: 'e,
+    {
+        match self {
+            Filter::Equals(field, value) => ctx
+                .get_field_value_unchecked(*field)
+                .is_some_and(|lhs| lhs == value),
+            Filter::And(lhs, rhs) => lhs.execute_unchecked(ctx) && rhs.execute_unchecked(ctx),
+            Filter::Or(lhs, rhs) => lhs.execute_unchecked(ctx) || rhs.execute_unchecked(ctx),
+            Filter::Not(inner) => !inner.execute_unchecked(ctx),
+        }
+    }
+}
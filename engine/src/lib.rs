@@ -0,0 +1,15 @@
+extern crate failure;
+
+#[macro_use]
+mod scheme;
+mod execution_context;
+mod filter;
+mod types;
+
+pub use execution_context::{
+    ExecutionContext, FieldValueTypeMismatchError, MissingFieldValuesError, MissingValuePolicy,
+    SetFieldValueError, UnknownFieldError,
+};
+pub use filter::Filter;
+pub use scheme::{Field, FieldCompatibilityIssue, Scheme, SchemeCompatibilityError};
+pub use types::{GetType, LhsValue, Type};
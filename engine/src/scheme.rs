@@ -0,0 +1,267 @@
+use execution_context::{FieldValueTypeMismatchError, SetFieldValueError, UnknownFieldError};
+use failure::Fail;
+use std::collections::HashMap;
+use std::fmt;
+use types::{GetType, LhsValue, Type};
+
+#[derive(Debug)]
+struct FieldDef {
+    name: String,
+    ty: Type,
+    default: Option<LhsValue<'static>>,
+}
+
+/// A named, typed field registered on a [`Scheme`](struct@Scheme), together
+/// with the index used to look its value up in an
+/// [`ExecutionContext`](::ExecutionContext)'s backing storage.
+#[derive(Debug, Clone, Copy)]
+pub struct Field<'s> {
+    scheme: &'s Scheme,
+    index: usize,
+}
+
+impl<'s> Field<'s> {
+    /// The scheme this field was looked up on.
+    pub fn scheme(&self) -> &'s Scheme {
+        self.scheme
+    }
+
+    /// This field's position in its scheme, used to index into an
+    /// [`ExecutionContext`](::ExecutionContext)'s backing storage.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The field's name.
+    pub fn name(&self) -> &'s str {
+        &self.scheme.fields[self.index].name
+    }
+
+    /// The type declared for this field in the scheme.
+    pub fn get_type(&self) -> Type {
+        self.scheme.fields[self.index].ty
+    }
+
+    /// The default value registered for this field on its scheme, if any.
+    ///
+    /// [`ExecutionContext::get_field_value_unchecked`](::ExecutionContext::get_field_value_unchecked)
+    /// falls back to this when the context itself has no runtime value for
+    /// the field, so a filter compiled against a newer scheme can still run
+    /// against a context that only populates a subset of its fields.
+    pub fn default_value(&self) -> Option<&'s LhsValue<'static>> {
+        self.scheme.fields[self.index].default.as_ref()
+    }
+}
+
+/// One field that keeps a [`Scheme`](struct@Scheme) from being a valid
+/// execution scheme for a filter built against another scheme.
+#[derive(Debug, PartialEq)]
+pub enum FieldCompatibilityIssue {
+    /// The field isn't registered at all on the execution scheme.
+    Missing {
+        /// The missing field's name.
+        name: String,
+    },
+    /// The field is registered, but with a different type.
+    TypeMismatch {
+        /// The field's name.
+        name: String,
+        /// The type the filter's scheme declared for this field.
+        expected: Type,
+        /// The type the execution scheme declared for this field instead.
+        found: Type,
+    },
+}
+
+impl fmt::Display for FieldCompatibilityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldCompatibilityIssue::Missing { name } => {
+                write!(f, "field `{}` is missing", name)
+            }
+            FieldCompatibilityIssue::TypeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "field `{}` should have {:?} type, but {:?} was found",
+                name, expected, found
+            ),
+        }
+    }
+}
+
+/// An error returned by [`Scheme::is_compatible_with`], enumerating every
+/// field of the filter's scheme that's missing or type-mismatched on the
+/// execution scheme.
+#[derive(Debug, PartialEq)]
+pub struct SchemeCompatibilityError {
+    /// The offending fields.
+    pub issues: Vec<FieldCompatibilityIssue>,
+}
+
+impl fmt::Display for SchemeCompatibilityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "scheme is not compatible: ")?;
+        for (i, issue) in self.issues.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl Fail for SchemeCompatibilityError {}
+
+/// Describes the set of fields a [`Filter`](::Filter) can reference and an
+/// [`ExecutionContext`](::ExecutionContext) can supply runtime values for.
+#[derive(Debug)]
+pub struct Scheme {
+    fields: Vec<FieldDef>,
+    indices: HashMap<String, usize>,
+}
+
+impl PartialEq for Scheme {
+    fn eq(&self, other: &Scheme) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Scheme {
+    /// Creates an empty scheme with no fields registered.
+    pub fn new() -> Self {
+        Scheme {
+            fields: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Registers a new field with the given name and type.
+    pub fn add_field(&mut self, name: impl Into<String>, ty: Type) {
+        let name = name.into();
+        let index = self.fields.len();
+
+        self.indices.insert(name.clone(), index);
+        self.fields.push(FieldDef {
+            name,
+            ty,
+            default: None,
+        });
+    }
+
+    /// Registers a default value for an already-registered field, type-checked
+    /// against the type it was declared with (the same check
+    /// [`ExecutionContext::set_field_value`](::ExecutionContext::set_field_value)
+    /// applies to runtime values). An `ExecutionContext` built on this scheme
+    /// falls back to this value when no runtime value was set for the field;
+    /// a context built on another compatible scheme uses that scheme's
+    /// default instead.
+    ///
+    /// Returns `SetFieldValueError::UnknownField` for a typo'd `name` rather
+    /// than panicking, same as `set_field_value`.
+    pub fn set_field_default(
+        &mut self,
+        name: &str,
+        default: LhsValue<'static>,
+    ) -> Result<(), SetFieldValueError> {
+        let index = *self.indices.get(name).ok_or_else(|| {
+            SetFieldValueError::UnknownField(UnknownFieldError {
+                name: name.to_owned(),
+                suggestion: None,
+            })
+        })?;
+        let field_type = self.fields[index].ty;
+        let value_type = default.get_type();
+
+        if field_type == value_type {
+            self.fields[index].default = Some(default);
+            Ok(())
+        } else {
+            Err(SetFieldValueError::TypeMismatch(
+                FieldValueTypeMismatchError {
+                    field_type,
+                    value_type,
+                },
+            ))
+        }
+    }
+
+    /// The number of fields registered on this scheme.
+    pub fn get_field_count(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Looks a field up by name.
+    pub fn get_field_index(&self, name: &str) -> Option<Field> {
+        self.indices
+            .get(name)
+            .map(|&index| Field { scheme: self, index })
+    }
+
+    /// Iterates over the `(name, type)` of every registered field.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Type)> {
+        self.fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.ty))
+    }
+
+    /// Checks that `self` is a valid *execution* scheme for a filter built
+    /// against `filter_scheme`, i.e. that `self` is a superset of
+    /// `filter_scheme`: every field `filter_scheme` declares must exist here
+    /// with a matching [`Type`]. `self` is always compatible with itself, and
+    /// is free to declare additional fields `filter_scheme` doesn't know
+    /// about.
+    ///
+    /// This lets one precompiled [`Filter`](::Filter) run against several
+    /// evolving [`ExecutionContext`](::ExecutionContext)s, rather than
+    /// requiring them to share the exact same `Scheme` instance.
+    pub fn is_compatible_with(
+        &self,
+        filter_scheme: &Scheme,
+    ) -> Result<(), SchemeCompatibilityError> {
+        if self == filter_scheme {
+            return Ok(());
+        }
+
+        let issues: Vec<_> = filter_scheme
+            .fields
+            .iter()
+            .filter_map(|field| match self.indices.get(&field.name) {
+                None => Some(FieldCompatibilityIssue::Missing {
+                    name: field.name.clone(),
+                }),
+                Some(&index) if self.fields[index].ty != field.ty => {
+                    Some(FieldCompatibilityIssue::TypeMismatch {
+                        name: field.name.clone(),
+                        expected: field.ty,
+                        found: self.fields[index].ty,
+                    })
+                }
+                Some(_) => None,
+            })
+            .collect();
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(SchemeCompatibilityError { issues })
+        }
+    }
+}
+
+/// Builds a [`Scheme`](struct@Scheme) from a `name: Type` list, e.g.
+/// `Scheme! { ip: Bytes, port: Int }`. Field names must be plain
+/// identifiers.
+#[macro_export]
+macro_rules! Scheme {
+    ($($name:ident : $ty:ident),* $(,)*) => {{
+        let mut scheme = $crate::Scheme::new();
+        $(
+            scheme.add_field(stringify!($name), $crate::Type::$ty);
+        )*
+        scheme
+    }};
+}
@@ -0,0 +1,40 @@
+/// The type of a field or a value, used to check that runtime values and
+/// filter comparisons match what a [`Scheme`](::Scheme) declares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    /// A signed integer.
+    Int,
+    /// A boolean.
+    Bool,
+    /// A byte string.
+    Bytes,
+}
+
+/// A runtime value for a field, set on an
+/// [`ExecutionContext`](::ExecutionContext) or compared against in a
+/// [`Filter`](::Filter).
+#[derive(Debug, Clone, PartialEq)]
+pub enum LhsValue<'e> {
+    /// A signed integer.
+    Int(i64),
+    /// A boolean.
+    Bool(bool),
+    /// A byte string.
+    Bytes(&'e [u8]),
+}
+
+/// Something that has a [`Type`](enum@Type).
+pub trait GetType {
+    /// Returns the type of `self`.
+    fn get_type(&self) -> Type;
+}
+
+impl<'e> GetType for LhsValue<'e> {
+    fn get_type(&self) -> Type {
+        match self {
+            LhsValue::Int(_) => Type::Int,
+            LhsValue::Bool(_) => Type::Bool,
+            LhsValue::Bytes(_) => Type::Bytes,
+        }
+    }
+}